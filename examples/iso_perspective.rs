@@ -55,7 +55,7 @@ fn startup(
         // Tile size
         vec2(256.0, 128.0),
     )
-    .with_padding(vec2(256.0, 128.0), vec2(256.0, 128.0), vec2(256.0, 128.0))
+    .with_atlas_padding(vec2(256.0, 128.0))
     // "Perspective" overhang draws the overlap of tiles depending on their "depth" that is the
     // y-axis of their world position (tiles higher up are considered further away).
     .with_projection(AXONOMETRIC)
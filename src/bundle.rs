@@ -19,8 +19,14 @@ pub struct MapBundle {
 
 impl MapBundle {
     pub fn new(map: Map, materials: &mut Assets<Map>) -> Self {
+        Self::from_handle(materials.add(map))
+    }
+
+    /// Construct a bundle from a map that has already been inserted into `Assets<Map>`, for
+    /// example a chunk spawned by [`crate::chunk::auto_spawn_chunks`].
+    pub fn from_handle(material: Handle<Map>) -> Self {
         Self {
-            material: materials.add(map),
+            material,
             ..default()
         }
     }
@@ -0,0 +1,384 @@
+//! Chunked, camera-driven streaming for maps too large to upload as a single texture.
+//!
+//! Build one with [`crate::MapBuilder::with_chunk_size`] + [`crate::MapBuilder::auto_spawn`]
+//! and spawn it as a [`ChunkedMapBundle`]; [`auto_spawn_chunks`] (registered by
+//! [`crate::FastTileMapPlugin`]) takes care of spawning/despawning chunk entities as cameras
+//! move, and [`ChunkedMap::indexer`] routes tile writes to whichever chunk owns them.
+
+use bevy::{
+    prelude::*,
+    utils::{HashMap, HashSet},
+};
+
+use crate::{
+    bundle::MapBundle,
+    map::{Map, MapIndexer, TileAnimation},
+    projection::Projection,
+};
+
+/// CPU-side state for one chunk, kept even while the chunk isn't spawned so edits made to
+/// off-screen regions aren't lost.
+struct ChunkState {
+    tiles: Vec<u16>,
+    entity: Option<Entity>,
+    handle: Option<Handle<Map>>,
+}
+
+impl ChunkState {
+    fn empty(tile_count: usize) -> Self {
+        Self {
+            tiles: vec![0u16; tile_count],
+            entity: None,
+            handle: None,
+        }
+    }
+}
+
+/// A large map split into `chunk_size`-tile chunks, each its own [`Map`] asset so only dirty
+/// chunks are re-uploaded to the GPU. Spawn as part of a [`ChunkedMapBundle`]; obtain one via
+/// [`crate::MapBuilder::build_chunked`].
+#[derive(Component)]
+pub struct ChunkedMap {
+    size: UVec2,
+    chunk_size: UVec2,
+    chunk_grid: UVec2,
+    texture: Handle<Image>,
+    tile_size: Vec2,
+    projection: Projection,
+    perspective_overhang: bool,
+    auto_spawn_radius: Option<f32>,
+    animations: Vec<TileAnimation>,
+    layer_count: u32,
+    chunks: HashMap<IVec2, ChunkState>,
+}
+
+impl ChunkedMap {
+    pub(crate) fn new(
+        size: UVec2,
+        chunk_size: UVec2,
+        texture: Handle<Image>,
+        tile_size: Vec2,
+        projection: Projection,
+        perspective_overhang: bool,
+        auto_spawn_radius: Option<f32>,
+        animations: Vec<TileAnimation>,
+        layer_count: u32,
+    ) -> Self {
+        let chunk_grid = UVec2::new(
+            (size.x + chunk_size.x - 1) / chunk_size.x,
+            (size.y + chunk_size.y - 1) / chunk_size.y,
+        );
+
+        Self {
+            size,
+            chunk_size,
+            chunk_grid,
+            texture,
+            tile_size,
+            projection,
+            perspective_overhang,
+            auto_spawn_radius,
+            animations: TileAnimation::ensure_non_empty(animations),
+            layer_count,
+            chunks: HashMap::default(),
+        }
+    }
+
+    fn chunk_tile_count(&self) -> usize {
+        (self.chunk_size.x * self.chunk_size.y * self.layer_count) as usize
+    }
+
+    fn local_index(&self, local: UVec2, layer: u32) -> usize {
+        ((layer * self.chunk_size.y + local.y) * self.chunk_size.x + local.x) as usize
+    }
+
+    /// Size of the whole (unchunked) map, in tiles.
+    pub fn size(&self) -> UVec2 {
+        self.size
+    }
+
+    /// Size of a single chunk, in tiles.
+    pub fn chunk_size(&self) -> UVec2 {
+        self.chunk_size
+    }
+
+    /// Obtain a [`MapIndexer`] that transparently routes reads/writes to whichever chunk
+    /// owns each tile, marking that chunk for re-upload if it is currently spawned.
+    pub fn indexer<'a>(&'a mut self, chunk_maps: &'a mut Assets<Map>) -> MapIndexer<'a> {
+        MapIndexer::chunked(ChunkedIndexer {
+            map: self,
+            chunk_maps,
+        })
+    }
+
+    fn chunk_world_size(&self) -> Vec2 {
+        self.chunk_size.as_vec2() * self.tile_size
+    }
+
+    fn chunk_in_bounds(&self, coord: IVec2) -> bool {
+        coord.x >= 0
+            && coord.y >= 0
+            && (coord.x as u32) < self.chunk_grid.x
+            && (coord.y as u32) < self.chunk_grid.y
+    }
+
+    fn split(&self, x: u32, y: u32) -> (IVec2, UVec2) {
+        let coord = IVec2::new(
+            (x / self.chunk_size.x) as i32,
+            (y / self.chunk_size.y) as i32,
+        );
+        let local = UVec2::new(x % self.chunk_size.x, y % self.chunk_size.y);
+        (coord, local)
+    }
+
+    /// Center of the chunk at `coord` (chunk entities' quads are centered on their own
+    /// `Transform`, like a plain [`Map`]'s), in the [`ChunkedMap`] entity's local space (chunk
+    /// entities are parented to it, so this becomes world-space once composed with the owner's
+    /// own transform).
+    fn chunk_origin(&self, coord: IVec2) -> Vec2 {
+        coord.as_vec2() * self.chunk_world_size()
+    }
+
+    fn build_chunk_map(&self, tiles: Vec<u16>) -> Map {
+        let mut builder = Map::builder(self.chunk_size, self.texture.clone(), self.tile_size)
+            .with_projection(self.projection)
+            .with_layer_count(self.layer_count);
+        if self.perspective_overhang {
+            builder = builder.with_perspective_overhang();
+        }
+
+        let mut map = builder.build();
+        map.tiles = tiles;
+        map.animations = self.animations.clone();
+        map.dirty = true;
+        map
+    }
+}
+
+/// A [`MapIndexer`] that writes through to a [`ChunkedMap`]'s owning chunk.
+pub(crate) struct ChunkedIndexer<'a> {
+    map: &'a mut ChunkedMap,
+    chunk_maps: &'a mut Assets<Map>,
+}
+
+impl<'a> ChunkedIndexer<'a> {
+    pub(crate) fn size(&self) -> UVec2 {
+        self.map.size
+    }
+
+    pub(crate) fn get(&self, x: u32, y: u32) -> u32 {
+        self.get_layer(x, y, 0)
+    }
+
+    pub(crate) fn set(&mut self, x: u32, y: u32, tile: u32) {
+        self.set_layer(x, y, 0, tile);
+    }
+
+    pub(crate) fn get_layer(&self, x: u32, y: u32, layer: u32) -> u32 {
+        if x >= self.map.size.x || y >= self.map.size.y || layer >= self.map.layer_count {
+            return 0;
+        }
+        let (coord, local) = self.map.split(x, y);
+        let Some(chunk) = self.map.chunks.get(&coord) else {
+            return 0;
+        };
+        chunk.tiles[self.map.local_index(local, layer)] as u32
+    }
+
+    pub(crate) fn set_layer(&mut self, x: u32, y: u32, layer: u32, tile: u32) {
+        if x >= self.map.size.x || y >= self.map.size.y || layer >= self.map.layer_count {
+            return;
+        }
+        let (coord, local) = self.map.split(x, y);
+        let idx = self.map.local_index(local, layer);
+        let tile_count = self.map.chunk_tile_count();
+        let chunk = self
+            .map
+            .chunks
+            .entry(coord)
+            .or_insert_with(|| ChunkState::empty(tile_count));
+
+        chunk.tiles[idx] = tile as u16;
+
+        if let Some(handle) = &chunk.handle {
+            if let Some(chunk_map) = self.chunk_maps.get_mut(handle) {
+                chunk_map.indexer().set_layer(local.x, local.y, layer, tile);
+            }
+        }
+    }
+}
+
+/// Bundle for spawning a [`ChunkedMap`]. Chunk entities are spawned/despawned as children by
+/// [`auto_spawn_chunks`].
+#[derive(Bundle)]
+pub struct ChunkedMapBundle {
+    pub chunked_map: ChunkedMap,
+    pub transform: Transform,
+    pub global_transform: GlobalTransform,
+    pub visibility: Visibility,
+    pub inherited_visibility: InheritedVisibility,
+    pub view_visibility: ViewVisibility,
+}
+
+impl ChunkedMapBundle {
+    pub fn new(chunked_map: ChunkedMap) -> Self {
+        Self {
+            chunked_map,
+            transform: Transform::default(),
+            global_transform: GlobalTransform::default(),
+            visibility: Visibility::default(),
+            inherited_visibility: InheritedVisibility::default(),
+            view_visibility: ViewVisibility::default(),
+        }
+    }
+}
+
+/// Spawn chunk entities within [`ChunkedMap`]'s auto-spawn radius of any camera, and despawn
+/// ones that have fallen out of range.
+pub fn auto_spawn_chunks(
+    mut commands: Commands,
+    mut chunked_maps: Query<(Entity, &mut ChunkedMap, &GlobalTransform)>,
+    cameras: Query<&GlobalTransform, With<Camera>>,
+    mut chunk_maps: ResMut<Assets<Map>>,
+) {
+    for (owner, mut chunked, owner_transform) in chunked_maps.iter_mut() {
+        let Some(radius) = chunked.auto_spawn_radius else {
+            continue;
+        };
+        let chunk_world_size = chunked.chunk_world_size();
+        let radius_chunks =
+            (radius / chunk_world_size.x.min(chunk_world_size.y)).ceil() as i32 + 1;
+
+        let mut wanted = HashSet::new();
+        for camera_transform in cameras.iter() {
+            let local =
+                camera_transform.translation().truncate() - owner_transform.translation().truncate();
+            // Chunks are always laid out on a plain rectangular grid (see `chunk_origin`)
+            // regardless of the map's tile projection, so this must not go through
+            // `projection::world_to_map`. `chunk_origin` centers chunk `coord` at
+            // `coord * chunk_world_size`, so the `+0.5` nudges `local` from a corner-relative
+            // offset onto that centering before truncating to a chunk coordinate.
+            let camera_coord = (local / chunk_world_size + Vec2::splat(0.5))
+                .floor()
+                .as_ivec2();
+
+            for dy in -radius_chunks..=radius_chunks {
+                for dx in -radius_chunks..=radius_chunks {
+                    let coord = camera_coord + IVec2::new(dx, dy);
+                    if chunked.chunk_in_bounds(coord) {
+                        wanted.insert(coord);
+                    }
+                }
+            }
+        }
+
+        let to_spawn: Vec<IVec2> = wanted
+            .iter()
+            .copied()
+            .filter(|coord| {
+                chunked
+                    .chunks
+                    .get(coord)
+                    .map(|c| c.entity.is_none())
+                    .unwrap_or(true)
+            })
+            .collect();
+
+        for coord in to_spawn {
+            let tile_count = chunked.chunk_tile_count();
+            let tiles = chunked
+                .chunks
+                .get(&coord)
+                .map(|c| c.tiles.clone())
+                .unwrap_or_else(|| vec![0u16; tile_count]);
+
+            let map = chunked.build_chunk_map(tiles);
+            let handle = chunk_maps.add(map);
+            let origin = chunked.chunk_origin(coord);
+
+            let entity = commands
+                .spawn(MapBundle {
+                    transform: Transform::from_translation(origin.extend(0.0)),
+                    ..MapBundle::from_handle(handle.clone())
+                })
+                .set_parent(owner)
+                .id();
+
+            let chunk = chunked
+                .chunks
+                .entry(coord)
+                .or_insert_with(|| ChunkState::empty(tile_count));
+            chunk.entity = Some(entity);
+            chunk.handle = Some(handle);
+        }
+
+        let to_despawn: Vec<IVec2> = chunked
+            .chunks
+            .iter()
+            .filter(|(coord, state)| state.entity.is_some() && !wanted.contains(coord))
+            .map(|(coord, _)| *coord)
+            .collect();
+
+        for coord in to_despawn {
+            if let Some(state) = chunked.chunks.get_mut(&coord) {
+                if let Some(entity) = state.entity.take() {
+                    commands.entity(entity).despawn_recursive();
+                }
+                state.handle = None;
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn test_map(size: UVec2, chunk_size: UVec2) -> ChunkedMap {
+        ChunkedMap::new(
+            size,
+            chunk_size,
+            Handle::default(),
+            Vec2::splat(16.0),
+            Projection::Square,
+            false,
+            None,
+            Vec::new(),
+            1,
+        )
+    }
+
+    #[test]
+    fn split_divides_tile_coords_into_chunk_and_local() {
+        let map = test_map(UVec2::new(20, 20), UVec2::new(8, 8));
+        assert_eq!(map.split(0, 0), (IVec2::new(0, 0), UVec2::new(0, 0)));
+        assert_eq!(map.split(7, 3), (IVec2::new(0, 0), UVec2::new(7, 3)));
+        assert_eq!(map.split(8, 3), (IVec2::new(1, 0), UVec2::new(0, 3)));
+        assert_eq!(map.split(19, 17), (IVec2::new(2, 2), UVec2::new(3, 1)));
+    }
+
+    #[test]
+    fn local_index_is_layer_major_then_row_major() {
+        let mut map = test_map(UVec2::new(8, 8), UVec2::new(4, 4));
+        map.layer_count = 2;
+        let layer_tiles = (4 * 4) as usize;
+        assert_eq!(map.local_index(UVec2::new(0, 0), 0), 0);
+        assert_eq!(map.local_index(UVec2::new(3, 0), 0), 3);
+        assert_eq!(map.local_index(UVec2::new(0, 1), 0), 4);
+        assert_eq!(map.local_index(UVec2::new(0, 0), 1), layer_tiles);
+    }
+
+    #[test]
+    fn chunk_origin_scales_by_chunk_world_size() {
+        let map = test_map(UVec2::new(32, 32), UVec2::new(8, 8));
+        assert_eq!(map.chunk_origin(IVec2::new(0, 0)), Vec2::new(0.0, 0.0));
+        assert_eq!(map.chunk_origin(IVec2::new(1, 0)), Vec2::new(128.0, 0.0));
+        assert_eq!(map.chunk_origin(IVec2::new(-1, 2)), Vec2::new(-128.0, 256.0));
+    }
+
+    #[test]
+    fn chunk_grid_rounds_up_for_a_partial_chunk() {
+        let map = test_map(UVec2::new(20, 9), UVec2::new(8, 8));
+        assert_eq!(map.chunk_grid, UVec2::new(3, 2));
+    }
+}
@@ -0,0 +1,19 @@
+//! A fast, simple tilemap renderer for bevy, using a single quad per map (or map chunk)
+//! and sampling the tile atlas in the fragment shader. This avoids the per-tile entity/sprite
+//! overhead of most other tilemap crates and keeps large maps cheap to render and update.
+
+pub mod bundle;
+pub mod chunk;
+pub mod map;
+pub mod map_builder;
+pub mod pathfinding;
+pub mod plugin;
+pub(crate) mod projection;
+pub(crate) mod shader;
+
+pub use bundle::MapBundle;
+pub use chunk::{ChunkedMap, ChunkedMapBundle};
+pub use map::{Map, MapAttributes};
+pub use map_builder::{MapBuilder, AXONOMETRIC, HEXAGONAL, SQUARE};
+pub use pathfinding::TileCostFn;
+pub use plugin::FastTileMapPlugin;
@@ -0,0 +1,483 @@
+use bevy::{
+    prelude::*,
+    reflect::TypePath,
+    render::render_resource::{
+        AsBindGroup, Extent3d, ShaderRef, ShaderType, TextureDimension, TextureFormat,
+    },
+    sprite::{Material2d, Mesh2dHandle},
+};
+
+use crate::{
+    chunk::ChunkedIndexer, map_builder::MapBuilder, pathfinding, pathfinding::PathfindingCache,
+    projection, shader::SHADER_HANDLE,
+};
+
+pub use crate::projection::Projection;
+
+/// GPU-side uniform mirroring the subset of [`Map`]'s configuration the shader needs.
+#[derive(Clone, Copy, Default, ShaderType)]
+pub(crate) struct MapUniform {
+    pub map_size: Vec2,
+    pub tile_size: Vec2,
+    pub atlas_padding: Vec2,
+    pub projection: u32,
+    pub perspective_overhang: u32,
+    /// Seconds on the shared [`TileAnimationTime`] clock (counted from plugin start, not per
+    /// map), advanced by [`advance_tile_animation_time`] and used by the shader to pick the
+    /// current frame of any animated tile.
+    pub time: f32,
+    /// Number of layers stacked in `tile_texture`, sampled back-to-front (layer 0 at the
+    /// bottom) by the shader. `1`
+    /// for a plain, single-layer map.
+    pub layer_count: u32,
+}
+
+/// One entry of a [`Map`]'s tile animation table: tile index `tile` cycles through
+/// `frame_count` atlas cells starting at `first_frame`, at `frames_per_second`.
+#[derive(Clone, Copy, Debug, Default, PartialEq, ShaderType)]
+pub(crate) struct TileAnimation {
+    pub tile: u32,
+    pub first_frame: u32,
+    pub frame_count: u32,
+    pub frames_per_second: f32,
+}
+
+impl TileAnimation {
+    /// Guarantee `animations` is never empty: a zero-length storage buffer isn't valid for
+    /// bind group creation, and the overwhelmingly common case is no registered animations at
+    /// all (see [`Map::layer_mix_color`]'s equivalent guard for the same kind of binding).
+    /// The placeholder's `tile: 0` never matches, since tile index `0` ("empty") is always
+    /// skipped before an animation lookup happens.
+    pub(crate) fn ensure_non_empty(animations: Vec<TileAnimation>) -> Vec<TileAnimation> {
+        if animations.is_empty() {
+            vec![TileAnimation::default()]
+        } else {
+            animations
+        }
+    }
+}
+
+/// A tilemap, rendered as a single quad (see [`crate::bundle::MapBundle`]) whose fragment
+/// shader samples tiles directly out of the atlas texture. Create one via [`Map::builder`].
+#[derive(Asset, AsBindGroup, TypePath, Clone)]
+pub struct Map {
+    #[uniform(0)]
+    pub(crate) uniform: MapUniform,
+    #[texture(1)]
+    #[sampler(2)]
+    pub(crate) atlas_texture: Handle<Image>,
+    #[texture(3, sample_type = "u_int", dimension = "2d_array")]
+    pub(crate) tile_texture: Handle<Image>,
+    #[storage(4, read_only)]
+    pub(crate) animations: Vec<TileAnimation>,
+    /// One entry per layer, multiplied into that layer's sampled color before compositing.
+    /// The per-vertex analogue of this is [`MapAttributes::mix_color`].
+    #[storage(5, read_only)]
+    pub(crate) layer_mix_color: Vec<Vec4>,
+
+    pub(crate) size: UVec2,
+    pub(crate) tile_size: Vec2,
+    pub(crate) projection: Projection,
+    pub(crate) perspective_overhang: bool,
+    pub(crate) layer_count: u32,
+
+    /// Tile indices, laid out layer-major then row-major:
+    /// `tiles[(layer * size.y + y) * size.x + x]`. `0` means "empty".
+    pub(crate) tiles: Vec<u16>,
+    /// Set whenever `tiles` has been written to since the last GPU upload.
+    pub(crate) dirty: bool,
+
+    /// Cost function and cached cost grid for [`Map::find_path`], registered via
+    /// [`crate::MapBuilder::with_pathfinding_cost`]. `None` if pathfinding isn't used.
+    pub(crate) pathfinding: Option<PathfindingCache>,
+}
+
+impl Material2d for Map {
+    fn vertex_shader() -> ShaderRef {
+        SHADER_HANDLE.typed().into()
+    }
+
+    fn fragment_shader() -> ShaderRef {
+        SHADER_HANDLE.typed().into()
+    }
+
+    fn alpha_mode(&self) -> AlphaMode {
+        AlphaMode::Blend
+    }
+}
+
+impl Map {
+    /// Start building a map of `size` tiles, sampling `tile_size`-sized cells out of `texture`.
+    pub fn builder(size: UVec2, texture: Handle<Image>, tile_size: Vec2) -> MapBuilder {
+        MapBuilder::new(size, texture, tile_size)
+    }
+
+    /// Size of the map, in tiles.
+    pub fn size(&self) -> UVec2 {
+        self.size
+    }
+
+    /// Size of a single tile, in world units.
+    pub fn tile_size(&self) -> Vec2 {
+        self.tile_size
+    }
+
+    /// Obtain a [`MapIndexer`] to read and write tiles. Writes mark the map dirty so the
+    /// tile texture is re-uploaded to the GPU on the next frame.
+    pub fn indexer(&mut self) -> MapIndexer {
+        MapIndexer {
+            target: IndexerTarget::Single(self),
+        }
+    }
+
+    /// Convert a world-space position into a (possibly fractional, for [`Projection::Square`]
+    /// and [`Projection::Axonometric`]) map coordinate.
+    pub fn world_to_map(&self, world: Vec2) -> Vec2 {
+        projection::world_to_map(self.projection, self.tile_size, world)
+    }
+
+    /// Convert a map coordinate (`z` carries an arbitrary "depth" input through unchanged)
+    /// into world space.
+    pub fn map_to_world_3d(&self, coord: Vec3) -> Vec3 {
+        projection::map_to_world_3d(self.projection, self.tile_size, coord)
+    }
+
+    /// Find the cheapest path between two world-space positions over layer `0`, using the cost
+    /// function registered with [`crate::MapBuilder::with_pathfinding_cost`]. Returns waypoints
+    /// as world-space tile centers, or `None` if pathfinding isn't configured, either endpoint
+    /// is outside the map or blocked, or no path exists.
+    pub fn find_path(&mut self, start_world: Vec2, goal_world: Vec2) -> Option<Vec<Vec2>> {
+        let Map {
+            size,
+            tile_size,
+            projection,
+            tiles,
+            pathfinding,
+            ..
+        } = self;
+        let tile_count = (size.x * size.y) as usize;
+        let pathfinding = pathfinding.as_mut()?;
+        pathfinding::find_path(
+            *projection,
+            *tile_size,
+            *size,
+            pathfinding,
+            &tiles[..tile_count],
+            start_world,
+            goal_world,
+        )
+    }
+}
+
+/// A view into a map's tiles, either a single [`Map`] or a whole
+/// [`crate::chunk::ChunkedMap`] (each write routed to the chunk that owns the tile). Obtained via [`Map::indexer`],
+/// [`MapBuilder::build_and_initialize`] or [`crate::chunk::ChunkedMap::indexer`].
+pub struct MapIndexer<'a> {
+    target: IndexerTarget<'a>,
+}
+
+pub(crate) enum IndexerTarget<'a> {
+    Single(&'a mut Map),
+    Chunked(ChunkedIndexer<'a>),
+}
+
+impl<'a> MapIndexer<'a> {
+    pub(crate) fn chunked(chunked: ChunkedIndexer<'a>) -> Self {
+        Self {
+            target: IndexerTarget::Chunked(chunked),
+        }
+    }
+
+    /// Size of the underlying map, in tiles.
+    pub fn size(&self) -> UVec2 {
+        match &self.target {
+            IndexerTarget::Single(map) => map.size,
+            IndexerTarget::Chunked(chunked) => chunked.size(),
+        }
+    }
+
+    /// Index of the tile at `(x, y)` on layer `0`, or `0` ("empty") if out of bounds.
+    pub fn get(&self, x: u32, y: u32) -> u32 {
+        self.get_layer(x, y, 0)
+    }
+
+    /// Set the tile at `(x, y)` on layer `0` to `tile`. Out-of-bounds writes are silently
+    /// ignored.
+    ///
+    /// For a [`crate::chunk::ChunkedMap`] this transparently routes the write to whichever
+    /// chunk owns `(x, y)`, marking it for re-upload if that chunk is currently spawned.
+    pub fn set(&mut self, x: u32, y: u32, tile: u32) {
+        self.set_layer(x, y, 0, tile);
+    }
+
+    /// Index of the tile at `(x, y)` on `layer`, or `0` ("empty") if out of bounds.
+    pub fn get_layer(&self, x: u32, y: u32, layer: u32) -> u32 {
+        match &self.target {
+            IndexerTarget::Single(map) => Self::index(map.size, map.layer_count, x, y, layer)
+                .map(|i| map.tiles[i] as u32)
+                .unwrap_or(0),
+            IndexerTarget::Chunked(chunked) => chunked.get_layer(x, y, layer),
+        }
+    }
+
+    /// Set the tile at `(x, y)` on `layer` to `tile`. Out-of-bounds writes (including an
+    /// out-of-range `layer`) are silently ignored.
+    ///
+    /// For a [`crate::chunk::ChunkedMap`] this transparently routes the write to whichever
+    /// chunk owns `(x, y)`, marking it for re-upload if that chunk is currently spawned.
+    pub fn set_layer(&mut self, x: u32, y: u32, layer: u32, tile: u32) {
+        match &mut self.target {
+            IndexerTarget::Single(map) => {
+                if let Some(i) = Self::index(map.size, map.layer_count, x, y, layer) {
+                    map.tiles[i] = tile as u16;
+                    map.dirty = true;
+                    if let Some(pathfinding) = &mut map.pathfinding {
+                        pathfinding.mark_dirty();
+                    }
+                }
+            }
+            IndexerTarget::Chunked(chunked) => chunked.set_layer(x, y, layer, tile),
+        }
+    }
+
+    fn index(size: UVec2, layer_count: u32, x: u32, y: u32, layer: u32) -> Option<usize> {
+        if x >= size.x || y >= size.y || layer >= layer_count {
+            return None;
+        }
+        Some(((layer * size.y + y) * size.x + x) as usize)
+    }
+}
+
+/// Attributes for a map's quad, applied without needing to re-upload tile data.
+#[derive(Component, Clone, Default)]
+pub struct MapAttributes {
+    /// Color multiplied into the sampled tile color, one entry per mesh vertex.
+    pub mix_color: Vec<Vec4>,
+    /// Color multiplied into each layer's sampled tile color before compositing, one entry
+    /// per layer (see [`crate::MapBuilder::with_layer_count`]); a single-layer map uses entry
+    /// `0`. Shorter than `layer_count`? Missing entries default to white. Longer? The extras
+    /// are dropped. An empty list leaves the map's current layer colors unchanged.
+    pub layer_mix_color: Vec<Vec4>,
+}
+
+/// Marker for a map entity whose [`Handle<Map>`] texture is still loading. Removed once the
+/// map's mesh has been set up.
+#[derive(Component, Clone, Copy, Debug, Default)]
+pub struct MapLoading;
+
+/// Marker for a map entity whose mesh is generated and kept in sync by this crate, rather
+/// than supplied by the user.
+#[derive(Component, Clone, Copy, Debug, Default)]
+pub struct MeshManagedByMap;
+
+/// Build the quad mesh for newly-loaded maps and remove [`MapLoading`].
+pub fn configure_loaded_assets(
+    mut commands: Commands,
+    materials: Res<Assets<Map>>,
+    images: Res<Assets<Image>>,
+    mut meshes: ResMut<Assets<Mesh>>,
+    query: Query<(Entity, &Handle<Map>), With<MapLoading>>,
+) {
+    for (entity, handle) in query.iter() {
+        let Some(map) = materials.get(handle) else {
+            continue;
+        };
+        if images.get(&map.atlas_texture).is_none() {
+            continue;
+        }
+
+        let size = map.size.as_vec2() * map.tile_size;
+        let mesh = Mesh::from(shape::Quad::new(size));
+
+        commands.entity(entity).insert((
+            Mesh2dHandle(meshes.add(mesh)),
+            MeshManagedByMap,
+        ));
+        commands.entity(entity).remove::<MapLoading>();
+    }
+}
+
+/// Build (or rebuild) the `R16Uint` array texture a [`Map`]'s tiles are uploaded into:
+/// `size.x` by `size.y` texels, one array layer per `layer_count`, laid out to match
+/// `tiles`' layer-major-then-row-major order.
+fn build_tile_texture(size: UVec2, layer_count: u32, tiles: &[u16]) -> Image {
+    let data = tiles.iter().flat_map(|tile| tile.to_le_bytes()).collect();
+    Image::new(
+        Extent3d {
+            width: size.x,
+            height: size.y,
+            depth_or_array_layers: layer_count,
+        },
+        TextureDimension::D2,
+        data,
+        TextureFormat::R16Uint,
+    )
+}
+
+/// Re-upload dirty maps' tile buffers to their GPU-side tile texture.
+pub fn update_loading_maps(mut materials: ResMut<Assets<Map>>, mut images: ResMut<Assets<Image>>) {
+    for (_, map) in materials.iter_mut() {
+        if !map.dirty {
+            continue;
+        }
+        let texture = build_tile_texture(map.size, map.layer_count, &map.tiles);
+        if let Some(existing) = images.get_mut(&map.tile_texture) {
+            *existing = texture;
+        } else {
+            map.tile_texture = images.add(texture);
+        }
+        map.dirty = false;
+    }
+}
+
+/// Log asset lifecycle events for maps, useful when diagnosing slow-to-load atlases.
+pub fn log_map_events(mut events: EventReader<AssetEvent<Map>>) {
+    for event in events.read() {
+        debug!("map asset event: {:?}", event);
+    }
+}
+
+/// Push [`MapAttributes`] into the mesh's vertex color attribute when it changes.
+pub fn update_map_vertex_attributes(
+    mut meshes: ResMut<Assets<Mesh>>,
+    query: Query<(&Mesh2dHandle, &MapAttributes), Changed<MapAttributes>>,
+) {
+    for (mesh_handle, attributes) in query.iter() {
+        let Some(mesh) = meshes.get_mut(&mesh_handle.0) else {
+            continue;
+        };
+        if attributes.mix_color.is_empty() {
+            continue;
+        }
+        mesh.insert_attribute(
+            Mesh::ATTRIBUTE_COLOR,
+            attributes
+                .mix_color
+                .iter()
+                .map(|c| c.to_array())
+                .collect::<Vec<_>>(),
+        );
+    }
+}
+
+/// Push [`MapAttributes::layer_mix_color`] into its map's per-layer color storage buffer
+/// when it changes.
+pub fn update_map_layer_colors(
+    mut materials: ResMut<Assets<Map>>,
+    query: Query<(&Handle<Map>, &MapAttributes), Changed<MapAttributes>>,
+) {
+    for (handle, attributes) in query.iter() {
+        let Some(map) = materials.get_mut(handle) else {
+            continue;
+        };
+        if attributes.layer_mix_color.is_empty() {
+            continue;
+        }
+        // The shader indexes `layer_mix_color[0..map.layer_count]` unconditionally, so pad a
+        // shorter list (e.g. "tint only layer 0") with opaque white and truncate a longer one
+        // rather than leaving the storage buffer shorter than the shader's read range.
+        let mut colors = attributes.layer_mix_color.clone();
+        colors.resize(map.layer_count as usize, Vec4::ONE);
+        map.layer_mix_color = colors;
+    }
+}
+
+/// Keep map entities' depth (`translation.z`) derived from their world `y` position so that
+/// [`MapBuilder::with_perspective_overhang`] sorts correctly against other maps/sprites.
+pub fn apply_map_transforms(
+    materials: Res<Assets<Map>>,
+    mut query: Query<(&Handle<Map>, &GlobalTransform, &mut Transform)>,
+) {
+    for (handle, global_transform, mut transform) in query.iter_mut() {
+        let Some(map) = materials.get(handle) else {
+            continue;
+        };
+        if !map.perspective_overhang {
+            continue;
+        }
+        transform.translation.z = -global_transform.translation().y * 0.001;
+    }
+}
+
+/// Running clock for tile animation, advanced by [`advance_tile_animation_time`] and copied
+/// into every [`Map`]'s uniform by [`update_tile_animation_time`] so the shader can compute
+/// the current frame of animated tiles without any CPU-side re-upload.
+#[derive(Resource, Default)]
+pub struct TileAnimationTime(pub f32);
+
+/// Advance [`TileAnimationTime`] by the frame's delta time.
+pub fn advance_tile_animation_time(time: Res<Time>, mut animation_time: ResMut<TileAnimationTime>) {
+    animation_time.0 += time.delta_seconds();
+}
+
+/// Copy [`TileAnimationTime`] into every map's uniform. Every map pays for this write; in the
+/// shader, every non-empty tile scans its map's animation table (a single placeholder entry
+/// when no animations are registered), and only tiles with an entry change frame.
+pub fn update_tile_animation_time(
+    animation_time: Res<TileAnimationTime>,
+    mut materials: ResMut<Assets<Map>>,
+) {
+    for (_, map) in materials.iter_mut() {
+        map.uniform.time = animation_time.0;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn ensure_non_empty_leaves_a_populated_table_alone() {
+        let animations = vec![TileAnimation {
+            tile: 3,
+            first_frame: 0,
+            frame_count: 4,
+            frames_per_second: 8.0,
+        }];
+        assert_eq!(TileAnimation::ensure_non_empty(animations.clone()), animations);
+    }
+
+    #[test]
+    fn ensure_non_empty_pads_an_empty_table_with_an_unmatchable_placeholder() {
+        let padded = TileAnimation::ensure_non_empty(Vec::new());
+        assert_eq!(padded.len(), 1);
+        // Tile index `0` ("empty") is always skipped before an animation lookup happens, so
+        // this placeholder entry can never match a real tile.
+        assert_eq!(padded[0].tile, 0);
+    }
+
+    fn test_map(size: UVec2, layer_count: u32) -> Map {
+        Map::builder(size, Handle::default(), Vec2::splat(16.0))
+            .with_layer_count(layer_count)
+            .build()
+    }
+
+    #[test]
+    fn get_set_layer_round_trips_within_bounds() {
+        let mut map = test_map(UVec2::new(4, 4), 3);
+        let mut indexer = map.indexer();
+        indexer.set_layer(1, 2, 2, 7);
+        assert_eq!(indexer.get_layer(1, 2, 2), 7);
+        // Writing one layer doesn't leak into its neighbours.
+        assert_eq!(indexer.get_layer(1, 2, 0), 0);
+        assert_eq!(indexer.get_layer(1, 2, 1), 0);
+    }
+
+    #[test]
+    fn get_layer_out_of_bounds_reads_as_empty() {
+        let map = test_map(UVec2::new(4, 4), 2);
+        let indexer = map.indexer();
+        assert_eq!(indexer.get_layer(4, 0, 0), 0);
+        assert_eq!(indexer.get_layer(0, 4, 0), 0);
+        assert_eq!(indexer.get_layer(0, 0, 2), 0);
+    }
+
+    #[test]
+    fn set_layer_out_of_bounds_is_silently_ignored() {
+        let mut map = test_map(UVec2::new(4, 4), 2);
+        let mut indexer = map.indexer();
+        indexer.set_layer(4, 0, 0, 9);
+        indexer.set_layer(0, 0, 2, 9);
+        assert!(map.tiles.iter().all(|&tile| tile == 0));
+    }
+}
@@ -0,0 +1,195 @@
+use std::sync::Arc;
+
+use bevy::prelude::*;
+
+use crate::{
+    chunk::ChunkedMap,
+    map::{Map, MapIndexer, MapUniform, Projection, TileAnimation},
+    pathfinding::{PathfindingCache, TileCostFn},
+};
+
+/// Flat, non-perspective grid projection. Each tile occupies a `tile_size` rectangle.
+pub const SQUARE: Projection = Projection::Square;
+/// Axonometric (diamond) projection, as used for classic isometric-looking tile art.
+pub const AXONOMETRIC: Projection = Projection::Axonometric;
+/// Pointy-top hexagonal projection using an offset-row grid.
+pub const HEXAGONAL: Projection = Projection::Hexagonal;
+
+/// Builder for [`Map`]. Obtain one via [`Map::builder`].
+pub struct MapBuilder {
+    size: UVec2,
+    texture: Handle<Image>,
+    tile_size: Vec2,
+    projection: Projection,
+    perspective_overhang: bool,
+    atlas_padding: Vec2,
+    chunk_size: Option<UVec2>,
+    auto_spawn_radius: Option<f32>,
+    animations: Vec<TileAnimation>,
+    layer_count: u32,
+    pathfinding_cost: Option<TileCostFn>,
+}
+
+impl MapBuilder {
+    pub fn new(size: UVec2, texture: Handle<Image>, tile_size: Vec2) -> Self {
+        Self {
+            size,
+            texture,
+            tile_size,
+            projection: Projection::Square,
+            perspective_overhang: false,
+            atlas_padding: Vec2::ZERO,
+            chunk_size: None,
+            auto_spawn_radius: None,
+            animations: Vec::new(),
+            layer_count: 1,
+            pathfinding_cost: None,
+        }
+    }
+
+    /// Reserve extra space between frames in the tile atlas, so adjacent frames' art doesn't
+    /// bleed into a sampled tile's edges.
+    pub fn with_atlas_padding(mut self, atlas: Vec2) -> Self {
+        self.atlas_padding = atlas;
+        self
+    }
+
+    /// Choose how map coordinates are projected into world space. See [`SQUARE`],
+    /// [`AXONOMETRIC`] and [`HEXAGONAL`].
+    pub fn with_projection(mut self, projection: Projection) -> Self {
+        self.projection = projection;
+        self
+    }
+
+    /// Draw tiles in depth order so that tiles further "down" the screen overhang tiles
+    /// further "up", instead of relying on draw order alone.
+    pub fn with_perspective_overhang(mut self) -> Self {
+        self.perspective_overhang = true;
+        self
+    }
+
+    /// Split the map into `chunk_size`-tile chunks, each uploaded to the GPU as its own
+    /// sub-texture. Required before calling [`Self::build_chunked`]; combine with
+    /// [`Self::auto_spawn`] to stream chunks in around the camera instead of spawning them
+    /// all up front.
+    pub fn with_chunk_size(mut self, chunk_size: UVec2) -> Self {
+        self.chunk_size = Some(chunk_size);
+        self
+    }
+
+    /// Automatically spawn/despawn chunk entities within `radius` world units of any camera.
+    /// Requires [`Self::with_chunk_size`].
+    pub fn auto_spawn(mut self, radius: f32) -> Self {
+        self.auto_spawn_radius = Some(radius);
+        self
+    }
+
+    /// Register a GPU-driven animation for `tile`: starting at `first_frame`, it cycles
+    /// through `frame_count` atlas cells at `frames_per_second`, entirely in the shader, so
+    /// tiles like water or torches animate without any per-frame CPU re-upload. Tiles with no
+    /// registered animation show their own atlas cell; the shader still scans the animation
+    /// table once for every non-empty tile it draws.
+    pub fn with_tile_animation(
+        mut self,
+        tile: u32,
+        first_frame: u32,
+        frame_count: u32,
+        frames_per_second: f32,
+    ) -> Self {
+        self.animations.push(TileAnimation {
+            tile,
+            first_frame,
+            frame_count: frame_count.max(1),
+            frames_per_second,
+        });
+        self
+    }
+
+    /// Stack `count` independent layers of tiles, sampled back-to-front (layer 0 at the bottom)
+    /// and alpha-composited in the shader, each layer over the ones below it. Useful for e.g. terrain plus overlays (walls, units) without spawning
+    /// an entity per overlay. Defaults to `1`.
+    pub fn with_layer_count(mut self, count: u32) -> Self {
+        self.layer_count = count.max(1);
+        self
+    }
+
+    /// Enable [`crate::Map::find_path`], using `cost` to decide the cost of entering a tile
+    /// (by its layer `0` index) or whether it's blocked entirely (`None`). Not supported on
+    /// [`Self::build_chunked`] maps; [`ChunkedMap`] has no pathfinding of its own yet, so
+    /// combining the two panics at build time rather than silently dropping `cost`.
+    pub fn with_pathfinding_cost(
+        mut self,
+        cost: impl Fn(u32) -> Option<f32> + Send + Sync + 'static,
+    ) -> Self {
+        self.pathfinding_cost = Some(Arc::new(cost));
+        self
+    }
+
+    /// Build the [`Map`], leaving all tiles at index 0.
+    pub fn build(self) -> Map {
+        let layer_count = self.layer_count.max(1);
+        let tile_count = (self.size.x * self.size.y * layer_count) as usize;
+
+        Map {
+            uniform: MapUniform {
+                map_size: self.size.as_vec2(),
+                tile_size: self.tile_size,
+                atlas_padding: self.atlas_padding,
+                projection: self.projection.shader_index(),
+                perspective_overhang: self.perspective_overhang as u32,
+                time: 0.0,
+                layer_count,
+            },
+            atlas_texture: self.texture,
+            tile_texture: Handle::default(),
+            animations: TileAnimation::ensure_non_empty(self.animations),
+            layer_mix_color: vec![Vec4::ONE; layer_count as usize],
+            size: self.size,
+            tile_size: self.tile_size,
+            projection: self.projection,
+            perspective_overhang: self.perspective_overhang,
+            layer_count,
+            tiles: vec![0u16; tile_count],
+            dirty: true,
+            pathfinding: self.pathfinding_cost.map(PathfindingCache::new),
+        }
+    }
+
+    /// Build the map and run `init` against a [`MapIndexer`] to fill in the initial tiles,
+    /// as a convenience over calling [`Map::indexer`] after the fact.
+    pub fn build_and_initialize(self, init: impl FnOnce(&mut MapIndexer)) -> Map {
+        let mut map = self.build();
+        init(&mut map.indexer());
+        map
+    }
+
+    /// Build a [`ChunkedMap`] instead of a single [`Map`], for maps too large (or too
+    /// frequently edited) to keep as one GPU texture. Panics if [`Self::with_chunk_size`]
+    /// was not called, was called with a zero component, or [`Self::with_pathfinding_cost`]
+    /// was called ([`ChunkedMap`] has no pathfinding support yet).
+    pub fn build_chunked(self) -> ChunkedMap {
+        let chunk_size = self
+            .chunk_size
+            .expect("with_chunk_size must be called before build_chunked");
+        assert!(
+            chunk_size.x > 0 && chunk_size.y > 0,
+            "with_chunk_size must be non-zero, got {chunk_size:?}"
+        );
+        assert!(
+            self.pathfinding_cost.is_none(),
+            "with_pathfinding_cost is not supported on chunked maps"
+        );
+
+        ChunkedMap::new(
+            self.size,
+            chunk_size,
+            self.texture,
+            self.tile_size,
+            self.projection,
+            self.perspective_overhang,
+            self.auto_spawn_radius,
+            self.animations,
+            self.layer_count.max(1),
+        )
+    }
+}
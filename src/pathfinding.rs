@@ -0,0 +1,271 @@
+//! Optional A* pathfinding over a [`crate::Map`]'s tiles. Register a per-tile cost function
+//! with [`crate::MapBuilder::with_pathfinding_cost`], then call [`crate::Map::find_path`] with
+//! world-space endpoints.
+
+use std::cmp::Ordering;
+use std::collections::{BinaryHeap, HashMap};
+use std::sync::Arc;
+
+use bevy::math::{IVec2, UVec2, Vec2};
+
+use crate::projection::{self, Projection};
+
+/// A per-tile movement cost: `Some(cost)` to allow entering a tile at that cost, `None` to
+/// block it entirely. Tile `0` ("empty") is a normal tile like any other as far as this
+/// function is concerned.
+pub type TileCostFn = Arc<dyn Fn(u32) -> Option<f32> + Send + Sync>;
+
+/// Cached per-tile costs for [`find_path`], rebuilt from a [`crate::Map`]'s layer `0` whenever
+/// [`PathfindingCache::mark_dirty`] has been called since the last path query.
+#[derive(Clone)]
+pub(crate) struct PathfindingCache {
+    cost_fn: TileCostFn,
+    costs: Vec<Option<f32>>,
+    dirty: bool,
+}
+
+impl PathfindingCache {
+    pub(crate) fn new(cost_fn: TileCostFn) -> Self {
+        Self {
+            cost_fn,
+            costs: Vec::new(),
+            dirty: true,
+        }
+    }
+
+    /// Invalidate the cached cost grid so it's rebuilt on the next [`find_path`] call. Called
+    /// whenever a [`crate::map::MapIndexer`] write may have changed layer `0`.
+    pub(crate) fn mark_dirty(&mut self) {
+        self.dirty = true;
+    }
+
+    fn refresh(&mut self, layer_0: &[u16]) {
+        if !self.dirty && self.costs.len() == layer_0.len() {
+            return;
+        }
+        self.costs = layer_0
+            .iter()
+            .map(|&tile| (self.cost_fn)(tile as u32))
+            .collect();
+        self.dirty = false;
+    }
+
+    fn cost(&self, size: UVec2, cell: IVec2) -> Option<f32> {
+        if cell.x < 0 || cell.y < 0 || cell.x as u32 >= size.x || cell.y as u32 >= size.y {
+            return None;
+        }
+        self.costs[(cell.y as u32 * size.x + cell.x as u32) as usize]
+    }
+}
+
+/// Find the cheapest path from `start_world` to `goal_world` over `layer_0`, using `cache`'s
+/// cost function. Returns waypoints as world-space tile centers, or `None` if either endpoint
+/// is outside the map / blocked, or no path exists.
+pub(crate) fn find_path(
+    projection: Projection,
+    tile_size: Vec2,
+    size: UVec2,
+    cache: &mut PathfindingCache,
+    layer_0: &[u16],
+    start_world: Vec2,
+    goal_world: Vec2,
+) -> Option<Vec<Vec2>> {
+    cache.refresh(layer_0);
+
+    let start = world_to_cell(projection, tile_size, size, start_world);
+    let goal = world_to_cell(projection, tile_size, size, goal_world);
+
+    cache.cost(size, start)?;
+    cache.cost(size, goal)?;
+
+    let cells = astar(projection, size, cache, start, goal)?;
+    Some(
+        cells
+            .into_iter()
+            .map(|cell| cell_to_world(projection, tile_size, size, cell))
+            .collect(),
+    )
+}
+
+fn world_to_cell(projection: Projection, tile_size: Vec2, size: UVec2, world: Vec2) -> IVec2 {
+    let map_pos = projection::world_to_map(projection, tile_size, world) + size.as_vec2() * 0.5;
+    map_pos.floor().as_ivec2()
+}
+
+fn cell_to_world(projection: Projection, tile_size: Vec2, size: UVec2, cell: IVec2) -> Vec2 {
+    let half_size = size.as_vec2() * 0.5;
+    // Square/Axonometric map coords are tile *corners*, so the cell (itself a corner) needs a
+    // `+0.5` nudge to land on its tile's center. Hexagonal map coords are already tile centers
+    // (see `map_to_world_hex`/`world_to_map_hex`), so shifting by an extra 0.5 would round to
+    // the wrong hex entirely. `world_to_cell` floors a hex center `h + size * 0.5` to
+    // `h + size / 2` (integer division), so that whole-tile offset is what is undone here.
+    let centered = match projection {
+        Projection::Square | Projection::Axonometric => cell.as_vec2() + Vec2::splat(0.5) - half_size,
+        Projection::Hexagonal => (cell - (size / 2).as_ivec2()).as_vec2(),
+    };
+    projection::map_to_world_3d(projection, tile_size, centered.extend(0.0)).truncate()
+}
+
+/// An entry in the A* open set, ordered by ascending `priority` (`f32` has no total order, but
+/// costs and heuristics here are always finite, so `NaN` never appears).
+struct OpenEntry {
+    priority: f32,
+    cell: IVec2,
+}
+
+impl PartialEq for OpenEntry {
+    fn eq(&self, other: &Self) -> bool {
+        self.priority == other.priority
+    }
+}
+
+impl Eq for OpenEntry {}
+
+impl Ord for OpenEntry {
+    fn cmp(&self, other: &Self) -> Ordering {
+        // Reversed so `BinaryHeap` (a max-heap) pops the lowest priority first.
+        other
+            .priority
+            .partial_cmp(&self.priority)
+            .unwrap_or(Ordering::Equal)
+    }
+}
+
+impl PartialOrd for OpenEntry {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+fn astar(
+    projection: Projection,
+    size: UVec2,
+    cache: &PathfindingCache,
+    start: IVec2,
+    goal: IVec2,
+) -> Option<Vec<IVec2>> {
+    let mut open = BinaryHeap::new();
+    let mut came_from: HashMap<IVec2, IVec2> = HashMap::new();
+    let mut g_score: HashMap<IVec2, f32> = HashMap::new();
+    // Cells index the tile grid, which is offset from map coordinates by `size / 2`. Hex row
+    // parity, and with it hex adjacency and distance, follows the map row, so neighbours and
+    // the heuristic are computed in map coordinates.
+    let offset = (size / 2).as_ivec2();
+
+    g_score.insert(start, 0.0);
+    open.push(OpenEntry {
+        priority: projection.heuristic(start - offset, goal - offset),
+        cell: start,
+    });
+
+    while let Some(OpenEntry { cell, .. }) = open.pop() {
+        if cell == goal {
+            return Some(reconstruct_path(&came_from, cell));
+        }
+
+        let current_g = g_score[&cell];
+        for neighbor in projection.neighbors(cell - offset) {
+            let neighbor = neighbor + offset;
+            let Some(step_cost) = cache.cost(size, neighbor) else {
+                continue;
+            };
+            let tentative_g = current_g + step_cost;
+            if tentative_g < *g_score.get(&neighbor).unwrap_or(&f32::INFINITY) {
+                came_from.insert(neighbor, cell);
+                g_score.insert(neighbor, tentative_g);
+                open.push(OpenEntry {
+                    priority: tentative_g + projection.heuristic(neighbor - offset, goal - offset),
+                    cell: neighbor,
+                });
+            }
+        }
+    }
+
+    None
+}
+
+fn reconstruct_path(came_from: &HashMap<IVec2, IVec2>, mut cell: IVec2) -> Vec<IVec2> {
+    let mut path = vec![cell];
+    while let Some(&prev) = came_from.get(&cell) {
+        cell = prev;
+        path.push(cell);
+    }
+    path.reverse();
+    path
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use bevy::math::{uvec2, vec2};
+
+    fn uniform_cache(size: UVec2, blocked: &[IVec2]) -> PathfindingCache {
+        let blocked = blocked.to_vec();
+        let mut cache = PathfindingCache::new(Arc::new(move |_tile| Some(1.0)));
+        cache.refresh(&vec![0u16; (size.x * size.y) as usize]);
+        for &cell in &blocked {
+            cache.costs[(cell.y as u32 * size.x + cell.x as u32) as usize] = None;
+        }
+        cache
+    }
+
+    #[test]
+    fn astar_finds_straight_line_on_empty_grid() {
+        let size = uvec2(5, 5);
+        let mut cache = uniform_cache(size, &[]);
+        let path = astar(Projection::Square, size, &mut cache, IVec2::ZERO, IVec2::new(4, 0))
+            .expect("path should exist");
+        assert_eq!(path.first(), Some(&IVec2::ZERO));
+        assert_eq!(path.last(), Some(&IVec2::new(4, 0)));
+        // 4-connected grid: shortest path is exactly 5 cells long.
+        assert_eq!(path.len(), 5);
+    }
+
+    #[test]
+    fn astar_routes_around_a_wall() {
+        let size = uvec2(5, 5);
+        // A vertical wall across x=2, except for a gap at y=4, forces a detour.
+        let wall: Vec<IVec2> = (0..4).map(|y| IVec2::new(2, y)).collect();
+        let mut cache = uniform_cache(size, &wall);
+        let path = astar(Projection::Square, size, &mut cache, IVec2::ZERO, IVec2::new(4, 0))
+            .expect("path should exist");
+        assert!(path.iter().all(|cell| !wall.contains(cell)));
+        assert_eq!(path.first(), Some(&IVec2::ZERO));
+        assert_eq!(path.last(), Some(&IVec2::new(4, 0)));
+    }
+
+    #[test]
+    fn astar_returns_none_when_goal_is_unreachable() {
+        let size = uvec2(3, 3);
+        // Fully sealed off goal.
+        let wall = [IVec2::new(1, 2), IVec2::new(2, 1)];
+        let mut cache = uniform_cache(size, &wall);
+        assert!(astar(Projection::Square, size, &mut cache, IVec2::ZERO, IVec2::new(2, 2)).is_none());
+    }
+
+    #[test]
+    fn world_cell_round_trip_for_square_projection() {
+        let tile_size = vec2(16.0, 16.0);
+        let size = uvec2(10, 10);
+        let cell = IVec2::new(3, -2);
+        let world = cell_to_world(Projection::Square, tile_size, size, cell);
+        assert_eq!(world_to_cell(Projection::Square, tile_size, size, world), cell);
+    }
+
+    #[test]
+    fn cell_to_world_lands_on_hex_center_not_the_row_above() {
+        // Hex cell (2, 3) at tile_size 16x16 has world center (40, 36) (see
+        // `map_to_world_hex`'s doc comment); `cell_to_world` must not add the
+        // square/axonometric "corner to center" 0.5 on top of that.
+        let tile_size = vec2(16.0, 16.0);
+        let size = uvec2(10, 10);
+        let cell = world_to_cell(
+            Projection::Hexagonal,
+            tile_size,
+            size,
+            vec2(40.0, 36.0),
+        );
+        let world = cell_to_world(Projection::Hexagonal, tile_size, size, cell);
+        assert_eq!(world, vec2(40.0, 36.0));
+    }
+}
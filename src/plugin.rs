@@ -1,6 +1,8 @@
+use crate::chunk::auto_spawn_chunks;
 use crate::map::{
-    apply_map_transforms, configure_loaded_assets, log_map_events, update_loading_maps,
-    update_map_vertex_attributes,
+    advance_tile_animation_time, apply_map_transforms, configure_loaded_assets, log_map_events,
+    update_loading_maps, update_map_layer_colors, update_map_vertex_attributes,
+    update_tile_animation_time, TileAnimationTime,
 };
 use bevy::{prelude::*, sprite::Material2dPlugin};
 
@@ -17,6 +19,7 @@ pub struct FastTileMapPlugin;
 impl Plugin for FastTileMapPlugin {
     fn build(&self, app: &mut App) {
         app.add_plugins(Material2dPlugin::<Map>::default());
+        app.init_resource::<TileAnimationTime>();
         let mut shaders = app.world.resource_mut::<Assets<Shader>>();
         shaders.insert(SHADER_HANDLE, Shader::from_wgsl(SHADER_CODE, file!()));
         app.add_systems(
@@ -24,8 +27,14 @@ impl Plugin for FastTileMapPlugin {
             (
                 (configure_loaded_assets, update_loading_maps, log_map_events).chain(),
                 update_map_vertex_attributes,
+                update_map_layer_colors,
             ),
         );
         app.add_systems(Update, apply_map_transforms);
+        app.add_systems(Update, auto_spawn_chunks);
+        app.add_systems(
+            Update,
+            (advance_tile_animation_time, update_tile_animation_time).chain(),
+        );
     }
 }
@@ -0,0 +1,214 @@
+use bevy::math::{ivec2, vec2, vec3, IVec2, Vec2, Vec3};
+use bevy::reflect::Reflect;
+
+/// Row spacing (as a fraction of `tile_size.y`) for [`Projection::Hexagonal`] so that
+/// neighbouring rows overlap the way pointy-top hexes do.
+const HEX_ROW_FACTOR: f32 = 0.75;
+
+/// How map coordinates are projected into world space. Shared between [`crate::map::Map`]
+/// and [`crate::chunk::ChunkedMap`] so both agree on tile placement.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, Reflect)]
+pub enum Projection {
+    /// Plain rectangular grid.
+    Square,
+    /// Diamond-shaped tiles, twice as wide as high.
+    Axonometric,
+    /// Pointy-top hexagons laid out in offset rows.
+    Hexagonal,
+}
+
+impl Projection {
+    pub(crate) fn shader_index(&self) -> u32 {
+        match self {
+            Projection::Square => 0,
+            Projection::Axonometric => 1,
+            Projection::Hexagonal => 2,
+        }
+    }
+
+    /// Grid-adjacent cells of the integer map coordinate `cell` (for the hex projection, row
+    /// parity is that of the map row), for grid-based algorithms like [`crate::pathfinding`]. [`Projection::Square`] and
+    /// [`Projection::Axonometric`] share a plain 4-connected grid; [`Projection::Hexagonal`] is
+    /// 6-connected via its offset-row layout (see [`map_to_world_hex`]).
+    pub(crate) fn neighbors(&self, cell: IVec2) -> Vec<IVec2> {
+        match self {
+            Projection::Square | Projection::Axonometric => {
+                SQUARE_DIRS.iter().map(|&dir| cell + dir).collect()
+            }
+            Projection::Hexagonal => hex_neighbors(cell),
+        }
+    }
+
+    /// Admissible pathfinding heuristic between two tile-grid cells: Manhattan distance for
+    /// [`Projection::Square`]/[`Projection::Axonometric`]'s 4-connected grid, hex distance
+    /// (shortest path length on the 6-connected grid) for [`Projection::Hexagonal`]. Both count
+    /// steps, so they are admissible only while every step costs at least `1`.
+    pub(crate) fn heuristic(&self, a: IVec2, b: IVec2) -> f32 {
+        match self {
+            Projection::Square | Projection::Axonometric => {
+                let d = (a - b).abs();
+                (d.x + d.y) as f32
+            }
+            Projection::Hexagonal => hex_distance(a, b) as f32,
+        }
+    }
+}
+
+const SQUARE_DIRS: [IVec2; 4] = [
+    IVec2::new(1, 0),
+    IVec2::new(-1, 0),
+    IVec2::new(0, 1),
+    IVec2::new(0, -1),
+];
+
+/// The six axial step directions, in the same `(q, r)` order used throughout hex grid
+/// literature (redblobgames).
+const HEX_AXIAL_DIRS: [(i32, i32); 6] = [(1, 0), (1, -1), (0, -1), (-1, 0), (-1, 1), (0, 1)];
+
+/// Offset-row `(x, y)` to axial `(q, r)`, the integer-grid counterpart of the float conversion
+/// in [`world_to_map_hex`].
+fn offset_to_axial(cell: IVec2) -> (i32, i32) {
+    let q = cell.x - (cell.y - (cell.y & 1)) / 2;
+    (q, cell.y)
+}
+
+/// Inverse of [`offset_to_axial`].
+fn axial_to_offset(q: i32, r: i32) -> IVec2 {
+    ivec2(q + (r - (r & 1)) / 2, r)
+}
+
+fn hex_neighbors(cell: IVec2) -> Vec<IVec2> {
+    let (q, r) = offset_to_axial(cell);
+    HEX_AXIAL_DIRS
+        .iter()
+        .map(|&(dq, dr)| axial_to_offset(q + dq, r + dr))
+        .collect()
+}
+
+/// Distance, in hex steps, between two offset-row cells.
+fn hex_distance(a: IVec2, b: IVec2) -> i32 {
+    let (aq, ar) = offset_to_axial(a);
+    let (bq, br) = offset_to_axial(b);
+    let (dq, dr) = (aq - bq, ar - br);
+    let ds = -dq - dr;
+    dq.abs().max(dr.abs()).max(ds.abs())
+}
+
+/// Convert a world-space position into a (possibly fractional, for [`Projection::Square`]
+/// and [`Projection::Axonometric`]) map coordinate.
+pub(crate) fn world_to_map(projection: Projection, tile_size: Vec2, world: Vec2) -> Vec2 {
+    match projection {
+        Projection::Square => world / tile_size,
+        Projection::Axonometric => {
+            let half = tile_size * 0.5;
+            vec2(
+                world.x / half.x + world.y / half.y,
+                world.y / half.y - world.x / half.x,
+            ) * 0.5
+        }
+        Projection::Hexagonal => world_to_map_hex(tile_size, world),
+    }
+}
+
+/// Convert a map coordinate (`z` carries an arbitrary "depth" input through unchanged) into
+/// world space.
+pub(crate) fn map_to_world_3d(projection: Projection, tile_size: Vec2, coord: Vec3) -> Vec3 {
+    match projection {
+        Projection::Square => vec3(coord.x * tile_size.x, coord.y * tile_size.y, coord.z),
+        Projection::Axonometric => {
+            let half = tile_size * 0.5;
+            vec3(
+                (coord.x - coord.y) * half.x,
+                (coord.x + coord.y) * half.y,
+                coord.z,
+            )
+        }
+        Projection::Hexagonal => map_to_world_hex(tile_size, coord),
+    }
+}
+
+/// Offset-row hex grid, pointy-top: `world_x = (x + 0.5 * (y & 1)) * tile_width`,
+/// `world_y = y * tile_height * row_factor`.
+fn map_to_world_hex(tile_size: Vec2, coord: Vec3) -> Vec3 {
+    let row = coord.y.round() as i32;
+    let world_x = (coord.x + 0.5 * (row & 1) as f32) * tile_size.x;
+    let world_y = row as f32 * tile_size.y * HEX_ROW_FACTOR;
+    vec3(world_x, world_y, coord.z)
+}
+
+/// Inverse of [`map_to_world_hex`]: go through axial coordinates so that rounding to the
+/// nearest hex doesn't have to special-case row parity.
+fn world_to_map_hex(tile_size: Vec2, world: Vec2) -> Vec2 {
+    let r_frac = world.y / (tile_size.y * HEX_ROW_FACTOR);
+    let q_frac = world.x / tile_size.x - r_frac * 0.5;
+    let s_frac = -q_frac - r_frac;
+
+    let (q, r) = round_hex_cube(q_frac, r_frac, s_frac);
+    let x = q + (r - (r & 1)) / 2;
+    vec2(x as f32, r as f32)
+}
+
+/// Round fractional cube coordinates to the nearest hex, fixing up the component with the
+/// largest rounding error so `q + r + s` stays `0`.
+fn round_hex_cube(q: f32, r: f32, s: f32) -> (i32, i32) {
+    let mut rq = q.round();
+    let mut rr = r.round();
+    let mut rs = s.round();
+
+    let q_diff = (rq - q).abs();
+    let r_diff = (rr - r).abs();
+    let s_diff = (rs - s).abs();
+
+    if q_diff > r_diff && q_diff > s_diff {
+        rq = -rr - rs;
+    } else if r_diff > s_diff {
+        rr = -rq - rs;
+    } else {
+        rs = -rq - rr;
+    }
+
+    debug_assert_eq!(rq + rr + rs, 0.0);
+    (rq as i32, rr as i32)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn round_hex_cube_snaps_to_nearest_hex() {
+        assert_eq!(round_hex_cube(0.0, 0.0, 0.0), (0, 0));
+        // Slightly off from (1, -1, 0); should still round to the same hex.
+        assert_eq!(round_hex_cube(1.1, -0.8, -0.3), (1, -1));
+        // (0.49, 0.49, -0.98) is actually closer to hex (0, 1, -1) than to (0, 0, 0) by cube
+        // distance, and the tie-break between the q and r components (both off by 0.49) picks
+        // `r`.
+        assert_eq!(round_hex_cube(0.49, 0.49, -0.98), (0, 1));
+    }
+
+    #[test]
+    fn offset_axial_round_trip() {
+        for y in -3..=3 {
+            for x in -3..=3 {
+                let cell = ivec2(x, y);
+                let (q, r) = offset_to_axial(cell);
+                assert_eq!(axial_to_offset(q, r), cell);
+            }
+        }
+    }
+
+    #[test]
+    fn hex_neighbors_are_six_and_distance_one() {
+        let cell = ivec2(2, 3);
+        let neighbors = hex_neighbors(cell);
+        assert_eq!(neighbors.len(), 6);
+        for neighbor in neighbors {
+            assert_eq!(hex_distance(cell, neighbor), 1);
+        }
+    }
+
+    #[test]
+    fn hex_distance_to_self_is_zero() {
+        assert_eq!(hex_distance(ivec2(5, -2), ivec2(5, -2)), 0);
+    }
+}
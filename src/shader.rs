@@ -0,0 +1,9 @@
+use bevy::prelude::*;
+
+/// WGSL source for [`crate::Map`]'s [`Material2d`](bevy::sprite::Material2d) implementation.
+pub const SHADER_CODE: &str = include_str!("shader.wgsl");
+
+/// Pre-registered handle for [`SHADER_CODE`], inserted into `Assets<Shader>` by
+/// [`crate::FastTileMapPlugin`] so [`crate::Map`] can reference it without loading from disk.
+pub const SHADER_HANDLE: Handle<Shader> =
+    Handle::weak_from_u128(0xB6C9A2F14E7D4B2A9C2E2F9B1A6E0001);